@@ -1,13 +1,20 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Write};
 
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
 
 use anyhow::{bail, Context, Result};
 use clap::Parser;
 
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
 #[derive(Parser)]
 #[command(
     author,
@@ -22,17 +29,180 @@ struct Cli {
     /// output file to write to
     #[clap(short, long)]
     output: Option<String>,
+
+    /// seed the shuffle for reproducible output
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// sort tracks by this key instead of shuffling them
+    #[clap(long, value_enum)]
+    sort: Option<SortKey>,
+
+    /// group tracks sharing this extended attribute (e.g. group-title) into
+    /// contiguous clusters, then shuffle the order of the clusters
+    #[clap(long = "group-by")]
+    group_by: Option<String>,
+
+    /// line-ending style for output
+    #[clap(long, value_enum, default_value = "lf")]
+    newline: NewlineStyle,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum NewlineStyle {
+    Lf,
+    Crlf,
+    /// reuse the dominant line ending detected in the input
+    Preserve,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SortKey {
+    Title,
+    Artist,
+    Duration,
+    Path,
+}
+
+/// The parsed contents of an `#EXTINF:` line: the duration, the display
+/// string (commonly `"Artist - Title"`), and any `key="value"` attributes
+/// modern extended M3U writers place between the duration and the comma,
+/// e.g. `#EXTINF:123 tvg-id="x" group-title="Rock",Artist - Title`.
+struct Extinf {
+    duration: Option<f64>,
+    attributes: Vec<(String, String)>,
+    title: String,
+}
+
+impl Extinf {
+    fn parse(line: &str) -> Extinf {
+        let rest = line
+            .trim_start_matches(EXTINF)
+            .trim_start_matches(':')
+            .trim_start();
+        let (meta, title) = split_at_top_level_comma(rest);
+        let mut parts = split_respecting_quotes(meta).into_iter();
+        let duration = parts.next().and_then(|d| d.parse::<f64>().ok());
+        let attributes = parts
+            .filter_map(|tok| {
+                let (key, value) = tok.split_once('=')?;
+                Some((key.to_string(), value.trim_matches('"').to_string()))
+            })
+            .collect();
+        Extinf {
+            duration,
+            attributes,
+            title: title.to_string(),
+        }
+    }
+}
+
+/// splits on the first comma that isn't inside a `"..."` span, since quoted
+/// attribute values (e.g. a multi-valued `group-title="Pop, Rock"`) may
+/// themselves contain commas
+fn split_at_top_level_comma(s: &str) -> (&str, &str) {
+    let mut in_quotes = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => return (&s[..i], &s[i + 1..]),
+            _ => {}
+        }
+    }
+    (s, "")
+}
+
+/// splits on whitespace, but keeps a `"..."` span (which may itself contain
+/// whitespace, e.g. `group-title="Pop, Rock"`) together as one token
+fn split_respecting_quotes(s: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut token_start: Option<usize> = None;
+    let mut in_quotes = false;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+            token_start.get_or_insert(i);
+        } else if c.is_whitespace() && !in_quotes {
+            if let Some(start) = token_start.take() {
+                tokens.push(&s[start..i]);
+            }
+        } else {
+            token_start.get_or_insert(i);
+        }
+    }
+    if let Some(start) = token_start {
+        tokens.push(&s[start..]);
+    }
+    tokens
+}
+
+impl fmt::Display for Extinf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", EXTINF)?;
+        if let Some(duration) = self.duration {
+            write!(f, "{}", duration)?;
+        }
+        for (key, value) in &self.attributes {
+            write!(f, " {}=\"{}\"", key, value)?;
+        }
+        write!(f, ",{}", self.title)
+    }
+}
+
+/// a line that preceded a track's path in the source file: either the
+/// `#EXTINF` line, parsed, or another directive (e.g. `#EXTGRP`,
+/// `#EXTVLCOPT`) kept verbatim
+enum Leading {
+    Extinf(Extinf),
+    Directive(String),
 }
 
 struct Track {
-    extinf: Option<String>,
+    /// leading lines in their original relative order, so reserialization
+    /// doesn't reshuffle `#EXTINF` against directives like `#EXTGRP`
+    leading: Vec<Leading>,
     path: String,
 }
 
+impl Track {
+    fn extinf(&self) -> Option<&Extinf> {
+        self.leading.iter().find_map(|l| match l {
+            Leading::Extinf(extinf) => Some(extinf),
+            Leading::Directive(_) => None,
+        })
+    }
+
+    fn title(&self) -> &str {
+        self.extinf().map(|e| e.title.as_str()).unwrap_or("")
+    }
+
+    /// derived from the `"Artist - Title"` convention of the display string
+    fn artist(&self) -> &str {
+        self.title()
+            .split_once(" - ")
+            .map_or(self.title(), |(artist, _)| artist)
+    }
+
+    fn duration(&self) -> Option<f64> {
+        self.extinf().and_then(|e| e.duration)
+    }
+
+    fn group_value(&self, attr: &str) -> Option<&str> {
+        self.extinf()?
+            .attributes
+            .iter()
+            .find(|(key, _)| key == attr)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
 impl fmt::Display for Track {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(extinf) = &self.extinf {
-            writeln!(f, "{}", extinf)?;
+        for leading in &self.leading {
+            match leading {
+                Leading::Extinf(extinf) => writeln!(f, "{}", extinf)?,
+                Leading::Directive(directive) => writeln!(f, "{}", directive)?,
+            }
         }
         write!(f, "{}", self.path)
     }
@@ -40,46 +210,117 @@ impl fmt::Display for Track {
 
 struct M3U {
     pub tracks: Vec<Track>,
+    /// the dominant line ending (`"\n"` or `"\r\n"`) seen while parsing,
+    /// used for `--newline preserve` output
+    dominant_newline: String,
 }
 
 impl M3U {
-    fn shuffle(&mut self) {
-        self.tracks.shuffle(&mut thread_rng());
+    fn shuffle(&mut self, rng: &mut impl Rng) {
+        self.tracks.shuffle(rng);
+    }
+
+    fn sort_by_key(&mut self, key: SortKey) {
+        self.tracks.sort_by(|a, b| match key {
+            SortKey::Title => a.title().cmp(b.title()),
+            SortKey::Artist => a.artist().cmp(b.artist()),
+            SortKey::Path => a.path.cmp(&b.path),
+            SortKey::Duration => a
+                .duration()
+                .partial_cmp(&b.duration())
+                .unwrap_or(std::cmp::Ordering::Equal),
+        });
+    }
+
+    /// clusters tracks sharing `attr`'s value into contiguous groups (tracks
+    /// missing the attribute form their own group), then shuffles the order
+    /// the groups appear in, leaving each group's members untouched
+    fn group_by(&mut self, attr: &str, rng: &mut impl Rng) {
+        let mut groups: Vec<(Option<String>, Vec<Track>)> = Vec::new();
+        let mut indices: HashMap<Option<String>, usize> = HashMap::new();
+        for track in self.tracks.drain(..) {
+            let value = track.group_value(attr).map(|v| v.to_string());
+            match indices.get(&value) {
+                Some(&i) => groups[i].1.push(track),
+                None => {
+                    indices.insert(value.clone(), groups.len());
+                    groups.push((value, vec![track]));
+                }
+            }
+        }
+        groups.shuffle(rng);
+        self.tracks = groups
+            .into_iter()
+            .flat_map(|(_, members)| members)
+            .collect();
     }
 }
 
 const EXTM3U: &str = "#EXTM3U";
 const EXTINF: &str = "#EXTINF";
 
+/// reads one line, reporting whether it was terminated by `\r\n` (vs. `\n`
+/// or EOF), so the dominant line ending of the input can be tracked
+fn read_line_with_terminator(buf: &mut dyn BufRead) -> Result<Option<(String, bool)>> {
+    let mut raw = String::new();
+    let n = buf.read_line(&mut raw).context("cannot read line")?;
+    if n == 0 {
+        return Ok(None);
+    }
+    if let Some(stripped) = raw.strip_suffix("\r\n") {
+        Ok(Some((stripped.to_string(), true)))
+    } else if let Some(stripped) = raw.strip_suffix('\n') {
+        Ok(Some((stripped.to_string(), false)))
+    } else {
+        Ok(Some((raw, false)))
+    }
+}
+
 impl TryFrom<Box<dyn BufRead>> for M3U {
     type Error = anyhow::Error;
 
-    fn try_from(buf: Box<dyn BufRead>) -> Result<M3U, anyhow::Error> {
-        let mut lines = buf.lines();
+    fn try_from(mut buf: Box<dyn BufRead>) -> Result<M3U, anyhow::Error> {
+        let mut crlf_count = 0usize;
+        let mut lf_count = 0usize;
+
         // make sure the first line is the header
-        if !lines
-            .next()
-            .context("cannot read empty input")?
-            .context("cannot read line")?
-            .starts_with(EXTM3U)
-        {
+        let (header, is_crlf) =
+            read_line_with_terminator(&mut *buf)?.context("cannot read empty input")?;
+        if is_crlf {
+            crlf_count += 1;
+        } else {
+            lf_count += 1;
+        }
+        if !header.starts_with(EXTM3U) {
             bail!("Missing #EXTM3U header");
         }
+
         let mut tracks = Vec::new();
-        let mut extinf = None;
-        for line in lines {
-            // bufread already trims newline properly
-            let ln = line.context("cannot read line")?.to_string();
+        let mut leading = Vec::new();
+        while let Some((ln, is_crlf)) = read_line_with_terminator(&mut *buf)? {
+            if is_crlf {
+                crlf_count += 1;
+            } else {
+                lf_count += 1;
+            }
             if ln.trim().is_empty() {
                 continue;
             } else if ln.starts_with(EXTINF) {
-                extinf = Some(ln);
+                leading.push(Leading::Extinf(Extinf::parse(&ln)));
+            } else if ln.starts_with('#') {
+                // a directive other than #EXTINF (e.g. #EXTGRP, #PLAYLIST,
+                // #EXTVLCOPT) that belongs to the next track, not a path
+                leading.push(Leading::Directive(ln));
             } else {
-                tracks.push(Track { extinf, path: ln });
-                extinf = None;
+                tracks.push(Track { leading, path: ln });
+                leading = Vec::new();
             }
         }
-        Ok(M3U { tracks })
+        let dominant_newline = if crlf_count > lf_count { "\r\n" } else { "\n" }.to_string();
+        Ok(M3U {
+            tracks,
+            dominant_newline,
+        })
     }
 }
 
@@ -93,6 +334,19 @@ impl fmt::Display for M3U {
     }
 }
 
+impl M3U {
+    /// serializes with the given line-ending style instead of the `"\n"`
+    /// that `Display` hardcodes. Track/path/attribute fields are always
+    /// single-line, so this is a safe, lossless substitution.
+    fn serialize(&self, newline: &str) -> String {
+        if newline == "\n" {
+            self.to_string()
+        } else {
+            self.to_string().replace('\n', newline)
+        }
+    }
+}
+
 fn main() -> Result<()> {
     let args = Cli::parse();
 
@@ -104,35 +358,111 @@ fn main() -> Result<()> {
     {
         // if args.file is None, read from STDIN
         let reader: Box<dyn BufRead> = match args.file {
-            Some(ref file) => Box::new(BufReader::new(
-                File::open(file).context(format!("Unable to open file to read from '{}'", file))?,
-            )),
-            None => Box::new(stdin.lock()),
+            Some(ref file) => {
+                let file_reader = BufReader::new(
+                    File::open(file)
+                        .context(format!("Unable to open file to read from '{}'", file))?,
+                );
+                if file.ends_with(".gz") {
+                    Box::new(BufReader::new(MultiGzDecoder::new(file_reader)))
+                } else {
+                    Box::new(file_reader)
+                }
+            }
+            None => {
+                let mut stdin_reader = stdin.lock();
+                let is_gzip = stdin_reader
+                    .fill_buf()
+                    .context("cannot read stdin")?
+                    .starts_with(&GZIP_MAGIC);
+                if is_gzip {
+                    Box::new(BufReader::new(MultiGzDecoder::new(stdin_reader)))
+                } else {
+                    Box::new(stdin_reader)
+                }
+            }
         };
 
         // parse
         m3u = reader.try_into().context("Unable to parse m3u file")?;
     }
-    // shuffle
-    m3u.shuffle();
+    // reorder: sort, group-by (shuffling cluster order), or a plain shuffle
+    if args.sort.is_some() && args.group_by.is_some() {
+        bail!("--sort cannot be combined with --group-by, which shuffles cluster order");
+    }
+    match (args.sort, &args.group_by) {
+        (Some(key), None) => m3u.sort_by_key(key),
+        (None, Some(attr)) => match args.seed {
+            Some(seed) => m3u.group_by(attr, &mut StdRng::seed_from_u64(seed)),
+            None => m3u.group_by(attr, &mut thread_rng()),
+        },
+        (None, None) => match args.seed {
+            Some(seed) => m3u.shuffle(&mut StdRng::seed_from_u64(seed)),
+            None => m3u.shuffle(&mut thread_rng()),
+        },
+        (Some(_), Some(_)) => unreachable!("checked above"),
+    }
+
+    let newline = match args.newline {
+        NewlineStyle::Lf => "\n",
+        NewlineStyle::Crlf => "\r\n",
+        NewlineStyle::Preserve => m3u.dominant_newline.as_str(),
+    };
 
     // scope to drop writer after writing, before program exits
     {
-        // write to file or STDOUT
-        let mut out: Box<dyn Write> = match args.output {
-            Some(ref file) => File::create(file)
-                .map(|f| Box::new(f) as Box<dyn Write>)
-                .context(format!("Unable to open file to write to '{}'", file))?,
-            None => Box::new(stdout.lock()),
+        let serialized = m3u.serialize(newline);
+        // gzip output needs `GzEncoder::finish()` called explicitly: `flush()`
+        // doesn't emit the trailing CRC32+len trailer, and the `Drop` impl
+        // that eventually calls it swallows any IO error, so a boxed
+        // `dyn Write` here would let a truncated .gz pass as success
+        let wrote = match args.output {
+            Some(ref file) if file.ends_with(".gz") => {
+                let file_writer = File::create(file)
+                    .context(format!("Unable to open file to write to '{}'", file))?;
+                let mut encoder = GzEncoder::new(file_writer, Compression::default());
+                write!(encoder, "{}", serialized)
+                    .context("Unable to write to output file")
+                    .and_then(|_| {
+                        encoder
+                            .finish()
+                            .map(|_| ())
+                            .context("Unable to finish writing gzip output file")
+                    })
+            }
+            Some(ref file) => {
+                let mut file_writer = File::create(file)
+                    .context(format!("Unable to open file to write to '{}'", file))?;
+                write!(file_writer, "{}", serialized)
+                    .context("Unable to write to output file")
+                    .and_then(|_| file_writer.flush().context("Unable to flush output file"))
+            }
+            None => {
+                let mut out = stdout.lock();
+                write!(out, "{}", serialized)
+                    .context("Unable to write to output file")
+                    .and_then(|_| out.flush().context("Unable to flush output file"))
+            }
         };
-
-        write!(out, "{}", m3u).context("Unable to write to output file")?;
-        out.flush()?;
+        if let Err(err) = wrote {
+            // downstream closed early (e.g. piped into `head`); match
+            // standard Unix tool behavior and exit cleanly instead of erroring
+            if is_broken_pipe(&err) {
+                return Ok(());
+            }
+            return Err(err);
+        }
     }
 
     Ok(())
 }
 
+fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<io::Error>())
+        .any(|io_err| io_err.kind() == io::ErrorKind::BrokenPipe)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,13 +476,120 @@ mod tests {
         assert_eq!(m3u.tracks.len(), 2);
         assert_eq!(m3u.tracks[0].path, "path/to/file1.mp3");
         assert_eq!(m3u.tracks[1].path, "path/to/file2.mp3");
+        let extinf0 = m3u.tracks[0].extinf().unwrap();
+        assert_eq!(extinf0.duration, Some(0.0));
+        assert_eq!(extinf0.title, "Artist1 - Title1");
+        let extinf1 = m3u.tracks[1].extinf().unwrap();
+        assert_eq!(extinf1.duration, Some(0.0));
+        assert_eq!(extinf1.title, "Artist2 - Title2");
+    }
+
+    #[test]
+    fn test_extinf_attributes() {
+        let ln = r#"#EXTINF:123 tvg-id="x" group-title="Rock",Artist - Title"#;
+        let extinf = Extinf::parse(ln);
+        assert_eq!(extinf.duration, Some(123.0));
+        assert_eq!(extinf.title, "Artist - Title");
         assert_eq!(
-            m3u.tracks[0].extinf,
-            Some("#EXTINF:0,Artist1 - Title1".to_string())
+            extinf.attributes,
+            vec![
+                ("tvg-id".to_string(), "x".to_string()),
+                ("group-title".to_string(), "Rock".to_string())
+            ]
+        );
+        assert_eq!(extinf.to_string(), ln);
+    }
+
+    #[test]
+    fn test_extinf_attribute_value_with_comma() {
+        let ln = r#"#EXTINF:100 group-title="Pop, Rock",Artist - Title"#;
+        let extinf = Extinf::parse(ln);
+        assert_eq!(extinf.duration, Some(100.0));
+        assert_eq!(extinf.title, "Artist - Title");
+        assert_eq!(
+            extinf.attributes,
+            vec![("group-title".to_string(), "Pop, Rock".to_string())]
+        );
+        assert_eq!(extinf.to_string(), ln);
+    }
+
+    fn directive_strings(track: &Track) -> Vec<&str> {
+        track
+            .leading
+            .iter()
+            .filter_map(|l| match l {
+                Leading::Directive(d) => Some(d.as_str()),
+                Leading::Extinf(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_directives_attach_to_next_track() {
+        let input = "#EXTM3U\n#EXTGRP:Rock\n#EXTINF:0,Artist1 - Title1\n#EXTVLCOPT:network-caching=1000\npath/to/file1.mp3\npath/to/file2.mp3";
+        let buf = Box::new(BufReader::new(input.as_bytes())) as Box<dyn BufRead>;
+        let m3u: M3U = M3U::try_from(buf).unwrap();
+        assert_eq!(m3u.tracks.len(), 2);
+        assert_eq!(
+            directive_strings(&m3u.tracks[0]),
+            vec!["#EXTGRP:Rock", "#EXTVLCOPT:network-caching=1000"]
+        );
+        assert!(directive_strings(&m3u.tracks[1]).is_empty());
+    }
+
+    #[test]
+    fn test_directives_round_trip_in_original_order() {
+        let input =
+            "#EXTM3U\n#PLAYLIST:My Mix\n#EXTGRP:Rock\n#EXTINF:0,Artist1 - Title1\npath/to/file1.mp3";
+        let buf = Box::new(BufReader::new(input.as_bytes())) as Box<dyn BufRead>;
+        let m3u: M3U = M3U::try_from(buf).unwrap();
+        assert_eq!(m3u.to_string(), format!("{}\n", input));
+    }
+
+    #[test]
+    fn test_sort_by_title() {
+        let input = "#EXTM3U\n#EXTINF:0,B Artist - Song\nb.mp3\n#EXTINF:0,A Artist - Song\na.mp3";
+        let buf = Box::new(BufReader::new(input.as_bytes())) as Box<dyn BufRead>;
+        let mut m3u: M3U = M3U::try_from(buf).unwrap();
+        m3u.sort_by_key(SortKey::Title);
+        assert_eq!(m3u.tracks[0].path, "a.mp3");
+        assert_eq!(m3u.tracks[1].path, "b.mp3");
+    }
+
+    #[test]
+    fn test_sort_by_artist() {
+        let input = "#EXTM3U\n#EXTINF:0,B Artist - Song\nb.mp3\n#EXTINF:0,A Artist - Song\na.mp3";
+        let buf = Box::new(BufReader::new(input.as_bytes())) as Box<dyn BufRead>;
+        let mut m3u: M3U = M3U::try_from(buf).unwrap();
+        m3u.sort_by_key(SortKey::Artist);
+        assert_eq!(m3u.tracks[0].path, "a.mp3");
+        assert_eq!(m3u.tracks[1].path, "b.mp3");
+    }
+
+    #[test]
+    fn test_group_by_keeps_members_contiguous() {
+        let input = concat!(
+            "#EXTM3U\n",
+            "#EXTINF:0 group-title=\"Rock\",A\n",
+            "rock1.mp3\n",
+            "#EXTINF:0 group-title=\"Jazz\",B\n",
+            "jazz1.mp3\n",
+            "#EXTINF:0 group-title=\"Rock\",C\n",
+            "rock2.mp3\n",
         );
+        let buf = Box::new(BufReader::new(input.as_bytes())) as Box<dyn BufRead>;
+        let mut m3u: M3U = M3U::try_from(buf).unwrap();
+        m3u.group_by("group-title", &mut StdRng::seed_from_u64(1));
+        let rock_positions: Vec<usize> = m3u
+            .tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.group_value("group-title") == Some("Rock"))
+            .map(|(i, _)| i)
+            .collect();
         assert_eq!(
-            m3u.tracks[1].extinf,
-            Some("#EXTINF:0,Artist2 - Title2".to_string())
+            rock_positions,
+            vec![rock_positions[0], rock_positions[0] + 1]
         );
     }
 
@@ -182,15 +619,70 @@ path/to/file1.mp3"#,
         let m3u: M3U = M3U::try_from(bufreader).unwrap();
         assert_eq!(m3u.tracks.len(), 1);
         assert_eq!(m3u.tracks[0].path, "path/to/file1.mp3");
-        assert_eq!(
-            m3u.tracks[0].extinf,
-            Some("#EXTINF:0,Artist1 - Title1".to_string())
-        );
+        let extinf0 = m3u.tracks[0].extinf().unwrap();
+        assert_eq!(extinf0.duration, Some(0.0));
+        assert_eq!(extinf0.title, "Artist1 - Title1");
 
         // reserialize to test if windows newline was removed
-        let out = M3U { tracks: m3u.tracks }.to_string();
+        let out = M3U {
+            tracks: m3u.tracks,
+            dominant_newline: "\n".to_string(),
+        }
+        .to_string();
         let mut ser = buf.clone().trim_end_matches(&['\r', '\n'][..]).to_string();
         ser.push_str("\n");
         assert_eq!(out, ser);
     }
+
+    #[test]
+    fn test_dominant_newline_detection() {
+        let input = "#EXTM3U\r\n#EXTINF:0,Artist1 - Title1\r\npath/to/file1.mp3\r\n";
+        let buf = Box::new(BufReader::new(input.as_bytes())) as Box<dyn BufRead>;
+        let m3u: M3U = M3U::try_from(buf).unwrap();
+        assert_eq!(m3u.dominant_newline, "\r\n");
+    }
+
+    #[test]
+    fn test_serialize_with_crlf() {
+        let file = File::open("testdata/basic.m3u").unwrap();
+        let buf = Box::new(BufReader::new(file)) as Box<dyn BufRead>;
+        let m3u: M3U = M3U::try_from(buf).unwrap();
+        let out = m3u.serialize("\r\n");
+        assert!(out.lines().next().is_some());
+        assert!(out.contains("\r\n"));
+        assert_eq!(out.replace("\r\n", "\n"), m3u.to_string());
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let file = File::open("testdata/basic.m3u").unwrap();
+        let buf = Box::new(BufReader::new(file)) as Box<dyn BufRead>;
+        let m3u: M3U = M3U::try_from(buf).unwrap();
+        let serialized = m3u.to_string();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(serialized.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let decoder = MultiGzDecoder::new(io::Cursor::new(gzipped));
+        let buf = Box::new(BufReader::new(decoder)) as Box<dyn BufRead>;
+        let round_tripped: M3U = M3U::try_from(buf).unwrap();
+
+        assert_eq!(round_tripped.to_string(), serialized);
+    }
+
+    #[test]
+    fn test_seeded_shuffle_is_reproducible() {
+        let file = File::open("testdata/basic.m3u").unwrap();
+        let buf = Box::new(BufReader::new(file)) as Box<dyn BufRead>;
+        let mut m3u_a: M3U = M3U::try_from(buf).unwrap();
+        m3u_a.shuffle(&mut StdRng::seed_from_u64(42));
+
+        let file = File::open("testdata/basic.m3u").unwrap();
+        let buf = Box::new(BufReader::new(file)) as Box<dyn BufRead>;
+        let mut m3u_b: M3U = M3U::try_from(buf).unwrap();
+        m3u_b.shuffle(&mut StdRng::seed_from_u64(42));
+
+        assert_eq!(m3u_a.to_string(), m3u_b.to_string());
+    }
 }
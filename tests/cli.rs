@@ -0,0 +1,158 @@
+//! Golden-file CLI integration tests: drive the built binary against the
+//! fixtures under `testdata/cli/*.txt` and assert its stdout and exit code.
+//! Shuffling is nondeterministic in general, so fixtures that exercise
+//! shuffling pin a `--seed` to stay reproducible.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use flate2::read::MultiGzDecoder;
+
+/// A fixture is a `-- <section> --` delimited text file with `args`,
+/// `stdin`, `stdout`, and `exit` sections. `args` is whitespace-split;
+/// the rest are taken verbatim (minus the trailing newline before the
+/// next marker). An `-- output-file --`/`-- output-contents --` pair is
+/// for fixtures that write to `-o` instead of stdout: `{output}` in
+/// `args` is substituted with a path under the OS temp dir, and after
+/// the run that path's contents (gunzipped first if it ends in `.gz`)
+/// are compared against `output-contents`.
+struct Fixture {
+    args: Vec<String>,
+    stdin: String,
+    stdout: String,
+    exit: i32,
+    output_file: Option<String>,
+    output_contents: String,
+}
+
+fn parse_fixture(contents: &str) -> Fixture {
+    let mut sections: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut current: Option<&str> = None;
+    for line in contents.lines() {
+        if let Some(name) = line.strip_prefix("-- ").and_then(|l| l.strip_suffix(" --")) {
+            current = Some(name);
+            sections.entry(name).or_default();
+        } else if let Some(name) = current {
+            sections.entry(name).or_default().push(line);
+        }
+    }
+    let join = |name: &str| -> String {
+        let lines = sections.get(name).cloned().unwrap_or_default();
+        if lines.is_empty() {
+            String::new()
+        } else {
+            format!("{}\n", lines.join("\n"))
+        }
+    };
+    let args = sections
+        .get("args")
+        .cloned()
+        .unwrap_or_default()
+        .join(" ")
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    let exit = sections
+        .get("exit")
+        .and_then(|lines| lines.first())
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0);
+    let output_file = sections
+        .get("output-file")
+        .and_then(|lines| lines.first())
+        .map(|s| s.trim().to_string());
+    Fixture {
+        args,
+        stdin: join("stdin"),
+        stdout: join("stdout"),
+        exit,
+        output_file,
+        output_contents: join("output-contents"),
+    }
+}
+
+fn run_fixture(path: &Path) {
+    let contents = fs::read_to_string(path).unwrap();
+    let mut fixture = parse_fixture(&contents);
+
+    let output_path = fixture.output_file.as_ref().map(|name| {
+        let resolved = std::env::temp_dir().join(format!(
+            "m3u-shuf-cli-test-{}-{}",
+            path.file_stem().unwrap().to_string_lossy(),
+            name
+        ));
+        for arg in fixture.args.iter_mut() {
+            if arg == "{output}" {
+                *arg = resolved.to_string_lossy().into_owned();
+            }
+        }
+        resolved
+    });
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_m3u-shuf"))
+        .args(&fixture.args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(fixture.stdin.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        fixture.stdout,
+        "stdout mismatch for {}",
+        path.display()
+    );
+    assert_eq!(
+        output.status.code(),
+        Some(fixture.exit),
+        "exit code mismatch for {}",
+        path.display()
+    );
+
+    if let Some(output_path) = output_path {
+        let written = fs::read(&output_path).unwrap();
+        let contents = if output_path.extension().and_then(|e| e.to_str()) == Some("gz") {
+            let mut decoder = MultiGzDecoder::new(written.as_slice());
+            let mut decoded = String::new();
+            std::io::Read::read_to_string(&mut decoder, &mut decoded).unwrap();
+            decoded
+        } else {
+            String::from_utf8(written).unwrap()
+        };
+        assert_eq!(
+            contents,
+            fixture.output_contents,
+            "output file contents mismatch for {}",
+            path.display()
+        );
+        fs::remove_file(&output_path).unwrap();
+    }
+}
+
+#[test]
+fn golden_files() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/cli");
+    let mut ran = 0;
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) == Some("txt") {
+            run_fixture(&path);
+            ran += 1;
+        }
+    }
+    assert!(ran > 0, "no fixtures found under {}", dir.display());
+}